@@ -1,63 +1,140 @@
-use std::{env, fs, io::Write, path::PathBuf};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
-fn ensure_windows_icon() {
-    // Create a minimal 1x1 transparent ICO if icons/icon.ico is missing.
+use image::{imageops::FilterType, Rgba, RgbaImage};
+
+// Brand fallback colour used when no source `icons/icon.png` is present.
+const BRAND: Rgba<u8> = Rgba([124, 77, 255, 255]);
+// Square sizes baked into the Windows `.ico`.
+const ICO_SIZES: [u32; 4] = [16, 32, 48, 256];
+
+fn icons_dir() -> PathBuf {
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".into());
-    let mut ico_path = PathBuf::from(manifest_dir);
-    ico_path.push("icons");
-    ico_path.push("icon.ico");
-
-    if ico_path.exists() {
-        return;
-    }
-
-    if let Some(parent) = ico_path.parent() {
-        let _ = fs::create_dir_all(parent);
-    }
-
-    // Minimal valid ICO bytes for a 1x1 pixel, 32-bit BGRA, with AND mask.
-    // Header (ICONDIR) + Directory (ICONDIRENTRY) + BITMAPINFOHEADER + pixel + mask
-    let ico_bytes: [u8; 70] = [
-        // ICONDIR
-        0x00, 0x00, // reserved
-        0x01, 0x00, // image type (icon)
-        0x01, 0x00, // number of images
-        // ICONDIRENTRY
-        0x01, // width
-        0x01, // height
-        0x00, // color count
-        0x00, // reserved
-        0x01, 0x00, // color planes
-        0x20, 0x00, // bits per pixel (32)
-        0x30, 0x00, 0x00, 0x00, // size of BMP data (48 bytes)
-        0x16, 0x00, 0x00, 0x00, // offset to BMP data (22 bytes)
-        // BITMAPINFOHEADER (40 bytes)
-        0x28, 0x00, 0x00, 0x00, // header size (40)
-        0x01, 0x00, 0x00, 0x00, // width = 1
-        0x02, 0x00, 0x00, 0x00, // height = 2 (image + mask)
-        0x01, 0x00, // planes = 1
-        0x20, 0x00, // bitcount = 32
-        0x00, 0x00, 0x00, 0x00, // compression = BI_RGB
-        0x00, 0x00, 0x00, 0x00, // size image = 0 (can be 0 for BI_RGB)
-        0x00, 0x00, 0x00, 0x00, // x pixels per meter
-        0x00, 0x00, 0x00, 0x00, // y pixels per meter
-        0x00, 0x00, 0x00, 0x00, // colors used
-        0x00, 0x00, 0x00, 0x00, // important colors
-        // Pixel data (BGRA) 1x1: transparent
-        0x00, 0x00, 0x00, 0x00,
-        // AND mask (padded to 32 bits)
-        0x00, 0x00, 0x00, 0x00,
-    ];
-
-    if let Ok(mut f) = fs::File::create(&ico_path) {
-        let _ = f.write_all(&ico_bytes);
+    let mut dir = PathBuf::from(manifest_dir);
+    dir.push("icons");
+    dir
+}
+
+// True when `out` is missing or older than `src`, i.e. it needs rebuilding.
+fn stale(src: &Path, out: &Path) -> bool {
+    let src_time = fs::metadata(src).and_then(|m| m.modified()).ok();
+    let out_time = fs::metadata(out).and_then(|m| m.modified()).ok();
+    match (src_time, out_time) {
+        (Some(s), Some(o)) => s > o,
+        _ => true,
+    }
+}
+
+// Load `icons/icon.png`, or synthesise a solid brand-colour square when the
+// source is absent so every build still has usable app imagery.
+fn load_source(icons: &Path) -> (RgbaImage, bool) {
+    let src = icons.join("icon.png");
+    if let Ok(img) = image::open(&src) {
+        (img.to_rgba8(), true)
+    } else {
+        (RgbaImage::from_pixel(256, 256, BRAND), false)
+    }
+}
+
+fn resized(base: &RgbaImage, size: u32) -> RgbaImage {
+    image::imageops::resize(base, size, size, FilterType::Lanczos3)
+}
+
+// Encode a multi-image `.ico` containing the standard square sizes.
+fn write_ico(base: &RgbaImage, out: &Path) {
+    let mut dir = ico::IconDir::new(ico::ResourceType::Icon);
+    for size in ICO_SIZES {
+        let scaled = resized(base, size);
+        let image = ico::IconImage::from_rgba_data(size, size, scaled.into_raw());
+        if let Ok(entry) = ico::IconDirEntry::encode(&image) {
+            dir.add_entry(entry);
+        }
+    }
+    if let Ok(file) = fs::File::create(out) {
+        let _ = dir.write(file);
+    }
+}
+
+// Draw a status dot in the lower-right corner of a 32px copy of the icon, used
+// for the tray overlays consumed by `include_image!` in `main.rs`.
+fn write_tray_icon(base: &RgbaImage, dot: Rgba<u8>, out: &Path) {
+    let mut img = resized(base, 32);
+    let (cx, cy, r) = (23i32, 23i32, 8i32);
+    for y in 0..32i32 {
+        for x in 0..32i32 {
+            let (dx, dy) = (x - cx, y - cy);
+            if dx * dx + dy * dy <= r * r {
+                img.put_pixel(x as u32, y as u32, dot);
+            }
+        }
     }
+    let _ = img.save(out);
+}
+
+// Regenerate the full set of app imagery from a single source, keeping
+// `build.rs` the one place that defines the application icons across platforms.
+fn synthesize_icons() {
+    let icons = icons_dir();
+    let _ = fs::create_dir_all(&icons);
+
+    let src = icons.join("icon.png");
+    let (base, from_file) = load_source(&icons);
+
+    // Persist the synthesised source so later builds are incremental.
+    if !from_file {
+        let _ = base.save(&src);
+    }
+
+    // Windows icon.
+    let ico = icons.join("icon.ico");
+    if stale(&src, &ico) {
+        write_ico(&base, &ico);
+    }
+
+    // PNG sizes Tauri expects for window/tray icons.
+    for (name, size) in [
+        ("32x32.png", 32u32),
+        ("128x128.png", 128),
+        ("128x128@2x.png", 256),
+        ("icon.png", 256),
+    ] {
+        let out = icons.join(name);
+        if name != "icon.png" && stale(&src, &out) {
+            let _ = resized(&base, size).save(&out);
+        }
+    }
+
+    // Tray overlays: green when running, red when crashed, neutral grey when
+    // stopped or given up.
+    let running = icons.join("tray-running.png");
+    let crashed = icons.join("tray-crashed.png");
+    let idle = icons.join("tray-idle.png");
+    if stale(&src, &running) {
+        write_tray_icon(&base, Rgba([46, 204, 113, 255]), &running);
+    }
+    if stale(&src, &crashed) {
+        write_tray_icon(&base, Rgba([231, 76, 60, 255]), &crashed);
+    }
+    if stale(&src, &idle) {
+        write_tray_icon(&base, Rgba([149, 165, 166, 255]), &idle);
+    }
+
+    println!("cargo:rerun-if-changed={}", src.display());
 }
 
 fn main() {
-    // Ensure Windows build has an .ico available
-    #[cfg(target_os = "windows")]
-    ensure_windows_icon();
+    // Expose the build target triple to the crate so the sidecar resolver can
+    // follow Tauri's `<name>-<triple>` naming convention at runtime. This is the
+    // triple we are building *for* (`TARGET`), which is what the bundled sidecar
+    // is named after even in cross-compiled bundles.
+    if let Ok(target) = env::var("TARGET") {
+        println!("cargo:rustc-env=TARGET_TRIPLE={}", target);
+    }
+
+    // Build all app imagery from a single source icon.
+    synthesize_icons();
 
     tauri_build::build()
 }
@@ -1,60 +1,595 @@
 // Prevents additional console window on Windows in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::process::{Child, Command};
-use std::sync::Mutex;
-use tauri::{Manager, State};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-// Backend process state
-struct BackendProcess(Mutex<Option<Child>>);
+use serde::Serialize;
+use shared_child::SharedChild;
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Tray icon id and the three embedded overlays reflecting backend health.
+const TRAY_ID: &str = "backend-status";
+const TRAY_ICON_RUNNING: Image<'static> = tauri::include_image!("icons/tray-running.png");
+const TRAY_ICON_CRASHED: Image<'static> = tauri::include_image!("icons/tray-crashed.png");
+// Neutral overlay for states that are neither healthy nor a crash: a clean stop
+// or the exhausted give-up state.
+const TRAY_ICON_IDLE: Image<'static> = tauri::include_image!("icons/tray-idle.png");
+
+// How long the backend must stay alive before we consider it healthy and
+// reset the restart backoff, and the bounds of the exponential backoff.
+const HEALTHY_AFTER: Duration = Duration::from_secs(10);
+const BACKOFF_BASE_MS: u64 = 500;
+const BACKOFF_CAP_MS: u64 = 30_000;
+const MAX_RESTART_ATTEMPTS: u32 = 8;
+// How long a graceful shutdown is allowed to run before we hard-kill.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+// Upper bound on the in-memory log history kept for backfilling a log panel.
+const LOG_RING_CAPACITY: usize = 1000;
+
+// A single captured line of backend output, forwarded on `backend://log` and
+// retained in the ring buffer.
+#[derive(Clone, Serialize)]
+struct LogLine {
+    // Which stream the line came from: "stdout" or "stderr", or "system" for
+    // messages the app itself records about the backend (e.g. shutdown).
+    stream: &'static str,
+    text: String,
+    // Milliseconds since the Unix epoch when the line was read.
+    ts: u64,
+}
+
+// Lifecycle of the supervised backend, mirrored to the frontend on the
+// `backend://status` event so a health indicator can follow along.
+#[derive(Clone, Copy, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+enum BackendStatus {
+    Starting,
+    Running { pid: u32 },
+    Crashed { code: Option<i32> },
+    Restarting { attempt: u32, delay_ms: u64 },
+    GaveUp { attempts: u32 },
+    Stopped,
+}
+
+// Backend process state shared between the supervisor thread and the Tauri
+// command handlers. The supervisor owns the `wait()` side of the child while
+// the main thread keeps a handle it can `kill()` concurrently, which is why we
+// store a `SharedChild` rather than a plain `std::process::Child`.
+struct BackendProcess {
+    child: Mutex<Option<Arc<SharedChild>>>,
+    // Set while we are intentionally tearing the backend down (window close,
+    // app exit or the tray "Quit" item) so the supervisor can tell a requested
+    // exit apart from a crash and stops supervising.
+    shutting_down: Arc<AtomicBool>,
+    // Set while the user has stopped the backend from the tray; the supervisor
+    // idles instead of respawning until it is cleared.
+    paused: Arc<AtomicBool>,
+    // Writer end of the backend's stdin, used to request a clean exit.
+    stdin: Mutex<Option<os_pipe::PipeWriter>>,
+    // Bounded history of recent backend output for backfilling a log panel.
+    logs: Arc<Mutex<VecDeque<LogLine>>>,
+}
+
+impl BackendProcess {
+    fn new() -> Self {
+        Self {
+            child: Mutex::new(None),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            stdin: Mutex::new(None),
+            logs: Arc::new(Mutex::new(VecDeque::with_capacity(LOG_RING_CAPACITY))),
+        }
+    }
+
+    // Kill the currently running child, if any. Used by the manual restart and
+    // stop commands and by the tray menu.
+    fn kill_current(&self) {
+        if let Ok(guard) = self.child.lock() {
+            if let Some(child) = guard.as_ref() {
+                let _ = child.kill();
+            }
+        }
+    }
+}
 
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::default().build())
+        .invoke_handler(tauri::generate_handler![
+            restart_backend,
+            stop_backend,
+            get_backend_logs
+        ])
         .setup(|app| {
-            // Start backend process
-            let backend_process = start_backend_process(app);
-            app.manage(BackendProcess(Mutex::new(backend_process)));
-
+            app.manage(BackendProcess::new());
+            setup_tray(app.handle())?;
+            spawn_supervisor(app.handle().clone());
             Ok(())
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { .. } = event {
-                // Clean up backend process before closing
-                if let Some(backend_state) = window.app_handle().try_state::<BackendProcess>() {
-                    if let Ok(mut backend) = backend_state.0.lock() {
-                        if let Some(mut child) = backend.take() {
-                            let _ = child.kill();
-                            println!("Backend process terminated");
+                shutdown_backend(window.app_handle(), SHUTDOWN_TIMEOUT);
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Guard against orphaning the backend no matter how the app exits.
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                shutdown_backend(app, SHUTDOWN_TIMEOUT);
+            }
+        });
+}
+
+// Two-phase backend shutdown: first ask the process to exit cleanly (a
+// `shutdown` line on its stdin), wait up to `timeout`, and only hard-kill if it
+// is still alive. Idempotent, so it is safe to run from both the window-close
+// handler and the process-exit guard.
+fn shutdown_backend(app: &AppHandle, timeout: Duration) {
+    let state = match app.try_state::<BackendProcess>() {
+        Some(state) => state,
+        None => return,
+    };
+
+    // Mark the teardown so the supervisor does not try to respawn.
+    if state.shutting_down.swap(true, Ordering::SeqCst) {
+        // Another path already ran the shutdown.
+        return;
+    }
+
+    let child = state
+        .child
+        .lock()
+        .ok()
+        .and_then(|guard| guard.clone());
+    let child = match child {
+        Some(child) => child,
+        None => return,
+    };
+
+    // Phase 1: request a clean exit.
+    if let Ok(mut guard) = state.stdin.lock() {
+        if let Some(writer) = guard.as_mut() {
+            let _ = writer.write_all(b"shutdown\n");
+            let _ = writer.flush();
+        }
+        // Closing stdin as well signals EOF to backends that exit on it.
+        *guard = None;
+    }
+
+    // Wait for the backend to exit on its own, up to the timeout.
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => {
+                push_log(app, "system", "Backend exited cleanly".into());
+                return;
+            }
+            Ok(None) => {
+                if Instant::now() >= deadline {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            Err(_) => break,
+        }
+    }
+
+    // Phase 2: hard kill.
+    let _ = child.kill();
+    push_log(app, "system", "Backend hard-killed after timeout".into());
+}
+
+// Force a restart of the backend. The running child is killed; the supervisor
+// loop handles the exit like any other unexpected exit (reported as `Crashed`)
+// and respawns it after the usual backoff delay. The backoff is not reset by
+// the restart itself.
+#[tauri::command]
+fn restart_backend(state: State<BackendProcess>) {
+    // Clear any pause so a previously stopped backend comes back, then kill the
+    // current child; the supervisor observes the exit and respawns it.
+    state.paused.store(false, Ordering::SeqCst);
+    state.kill_current();
+}
+
+// Stop the backend and leave it stopped until a restart is requested.
+#[tauri::command]
+fn stop_backend(state: State<BackendProcess>) {
+    state.paused.store(true, Ordering::SeqCst);
+    state.kill_current();
+}
+
+// Return the retained backend log history so a log panel can backfill on open.
+#[tauri::command]
+fn get_backend_logs(state: State<BackendProcess>) -> Vec<LogLine> {
+    state
+        .logs
+        .lock()
+        .map(|logs| logs.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+// Spawn the monitor thread that owns the backend lifecycle: it launches the
+// child, waits for it to exit, and restarts it with exponential backoff until
+// it either stays healthy or we give up.
+fn spawn_supervisor(app: AppHandle) {
+    thread::spawn(move || {
+        let mut attempts: u32 = 0;
+
+        loop {
+            // A stop requested while no child was running (during a backoff
+            // sleep) had nothing to kill; honour it here instead of respawning.
+            if let Some(state) = app.try_state::<BackendProcess>() {
+                if state.paused.load(Ordering::SeqCst) {
+                    emit_status(&app, BackendStatus::Stopped);
+                    if park_until_restart(&app) {
+                        break;
+                    }
+                    attempts = 0;
+                }
+            }
+
+            emit_status(
+                &app,
+                if attempts == 0 {
+                    BackendStatus::Starting
+                } else {
+                    BackendStatus::Restarting {
+                        attempt: attempts,
+                        delay_ms: 0,
+                    }
+                },
+            );
+
+            let child = match start_backend_process(&app) {
+                Some(child) => Arc::new(child),
+                None => {
+                    // We could not even spawn; treat it as a crash and back off.
+                    attempts += 1;
+                    if attempts > MAX_RESTART_ATTEMPTS {
+                        emit_status(&app, BackendStatus::GaveUp { attempts });
+                        if park_until_restart(&app) {
+                            break;
                         }
+                        attempts = 0;
+                        continue;
                     }
+                    let delay = backoff_delay(attempts);
+                    emit_status(
+                        &app,
+                        BackendStatus::Restarting {
+                            attempt: attempts,
+                            delay_ms: delay,
+                        },
+                    );
+                    thread::sleep(Duration::from_millis(delay));
+                    // A shutdown may have landed while we slept with no child to
+                    // kill; bail before spawning a fresh one.
+                    if is_shutting_down(&app) {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            emit_status(&app, BackendStatus::Running { pid: child.id() });
+
+            // Publish the child so the main thread can kill it.
+            if let Some(state) = app.try_state::<BackendProcess>() {
+                if let Ok(mut guard) = state.child.lock() {
+                    *guard = Some(child.clone());
+                }
+            }
+
+            let started = Instant::now();
+            let exit = child.wait();
+
+            // Drop our reference so a killed child does not linger in state.
+            if let Some(state) = app.try_state::<BackendProcess>() {
+                if let Ok(mut guard) = state.child.lock() {
+                    *guard = None;
+                }
+                if state.shutting_down.load(Ordering::SeqCst) {
+                    // Intentional teardown on app exit: stop supervising.
+                    break;
+                }
+                if state.paused.load(Ordering::SeqCst) {
+                    // User stopped the backend: idle until resumed rather than
+                    // treating the exit as a crash.
+                    emit_status(&app, BackendStatus::Stopped);
+                    loop {
+                        thread::sleep(Duration::from_millis(200));
+                        if state.shutting_down.load(Ordering::SeqCst) {
+                            return;
+                        }
+                        if !state.paused.load(Ordering::SeqCst) {
+                            break;
+                        }
+                    }
+                    attempts = 0;
+                    continue;
+                }
+            }
+
+            // A run that stayed alive long enough is considered healthy, so the
+            // next failure starts the backoff fresh.
+            if started.elapsed() >= HEALTHY_AFTER {
+                attempts = 0;
+            }
+
+            let code = exit.ok().and_then(|status| status.code());
+            emit_status(&app, BackendStatus::Crashed { code });
+
+            attempts += 1;
+            if attempts > MAX_RESTART_ATTEMPTS {
+                emit_status(&app, BackendStatus::GaveUp { attempts });
+                // Park rather than exit the supervisor: otherwise the tray
+                // "Restart backend" item and `restart_backend` would have no
+                // live loop to respawn from. A manual restart clears the pause
+                // and we resume with a fresh backoff budget.
+                if park_until_restart(&app) {
+                    break;
+                }
+                attempts = 0;
+                continue;
+            }
+
+            let delay = backoff_delay(attempts);
+            emit_status(
+                &app,
+                BackendStatus::Restarting {
+                    attempt: attempts,
+                    delay_ms: delay,
+                },
+            );
+            thread::sleep(Duration::from_millis(delay));
+            // A shutdown requested while we slept here finds the child already
+            // gone, so `shutdown_backend` kills nothing; bail before the loop
+            // spawns a fresh backend during teardown.
+            if is_shutting_down(&app) {
+                break;
+            }
+        }
+    });
+}
+
+// Whether an intentional teardown is in progress, used by the supervisor to
+// avoid respawning after a backoff sleep.
+fn is_shutting_down(app: &AppHandle) -> bool {
+    app.try_state::<BackendProcess>()
+        .map(|state| state.shutting_down.load(Ordering::SeqCst))
+        .unwrap_or(true)
+}
+
+// `min(base * 2^(attempt - 1), cap)` in milliseconds.
+fn backoff_delay(attempt: u32) -> u64 {
+    BACKOFF_BASE_MS
+        .saturating_mul(1u64 << (attempt - 1).min(16))
+        .min(BACKOFF_CAP_MS)
+}
+
+// Idle the supervisor after it has given up, reusing the same pause flag the
+// manual stop uses: a restart (`restart_backend` or the tray item) clears
+// `paused` and we resume. Returns true if the app is shutting down and the
+// supervisor should exit for good.
+fn park_until_restart(app: &AppHandle) -> bool {
+    let state = match app.try_state::<BackendProcess>() {
+        Some(state) => state,
+        None => return true,
+    };
+    state.paused.store(true, Ordering::SeqCst);
+    loop {
+        thread::sleep(Duration::from_millis(200));
+        if state.shutting_down.load(Ordering::SeqCst) {
+            return true;
+        }
+        if !state.paused.load(Ordering::SeqCst) {
+            return false;
+        }
+    }
+}
+
+fn emit_status(app: &AppHandle, status: BackendStatus) {
+    let _ = app.emit("backend://status", status);
+    update_tray_icon(app, status);
+}
+
+// Reflect the supervisor state in the tray: green while healthy, a neutral
+// overlay for a clean stop or give-up, and red for a crash as well as while
+// starting or restarting.
+fn update_tray_icon(app: &AppHandle, status: BackendStatus) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let icon = match status {
+            BackendStatus::Running { .. } => TRAY_ICON_RUNNING,
+            BackendStatus::Stopped | BackendStatus::GaveUp { .. } => TRAY_ICON_IDLE,
+            _ => TRAY_ICON_CRASHED,
+        };
+        let _ = tray.set_icon(Some(icon));
+    }
+}
+
+// Build the system tray with a status icon and a control menu wired to the same
+// `BackendProcess` state used elsewhere.
+fn setup_tray(app: &AppHandle) -> tauri::Result<()> {
+    let restart = MenuItem::with_id(app, "restart", "Restart backend", true, None::<&str>)?;
+    let stop = MenuItem::with_id(app, "stop", "Stop backend", true, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", "Show window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&restart, &stop, &show, &quit])?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(TRAY_ICON_CRASHED)
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "restart" => {
+                if let Some(state) = app.try_state::<BackendProcess>() {
+                    state.paused.store(false, Ordering::SeqCst);
+                    state.kill_current();
                 }
             }
+            "stop" => {
+                if let Some(state) = app.try_state::<BackendProcess>() {
+                    state.paused.store(true, Ordering::SeqCst);
+                    state.kill_current();
+                }
+            }
+            "show" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            "quit" => {
+                if let Some(state) = app.try_state::<BackendProcess>() {
+                    state.shutting_down.store(true, Ordering::SeqCst);
+                    state.kill_current();
+                }
+                app.exit(0);
+            }
+            _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(app)?;
+
+    Ok(())
 }
 
-fn start_backend_process(app: &tauri::App) -> Option<Child> {
-    // Get the resource directory where sidecar binaries are located
-    let resource_dir = app
-        .path()
-        .resource_dir()
-        .expect("Failed to get resource directory");
+// Base name of the bundled backend sidecar, without triple suffix or
+// extension.
+const BACKEND_NAME: &str = "MegaMelangeBackend";
 
-    // Path to the backend executable (will be bundled as sidecar)
-    let backend_exe = resource_dir.join("MegaMelangeBackend.exe");
+// Error returned when the sidecar binary cannot be located. It carries every
+// path we probed so failures are actionable on platforms where the expected
+// layout differs.
+struct SidecarNotFound {
+    base_name: String,
+    tried: Vec<PathBuf>,
+}
+
+impl fmt::Display for SidecarNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "could not resolve sidecar '{}'; tried:", self.base_name)?;
+        for path in &self.tried {
+            write!(f, "\n  {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+// Resolve a bundled sidecar binary by Tauri's naming convention: the build
+// target triple is appended to the base name (and `.exe` on Windows), falling
+// back to the bare name. Both the resource directory and the executable's own
+// directory are searched so dev and bundled layouts both work.
+fn resolve_sidecar(app: &AppHandle, base_name: &str) -> Result<PathBuf, SidecarNotFound> {
+    let triple = env!("TARGET_TRIPLE");
+    let ext = if cfg!(windows) { ".exe" } else { "" };
+
+    // Candidate file names, most specific first.
+    let file_names = [
+        format!("{base_name}-{triple}{ext}"),
+        format!("{base_name}{ext}"),
+    ];
+
+    // Directories to search.
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        dirs.push(resource_dir);
+    }
+    if let Ok(exe) = std::env::current_exe() {
+        if let Some(parent) = exe.parent() {
+            dirs.push(parent.to_path_buf());
+        }
+    }
+
+    let mut tried = Vec::new();
+    for dir in &dirs {
+        for name in &file_names {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                return Ok(candidate);
+            }
+            tried.push(candidate);
+        }
+    }
+
+    Err(SidecarNotFound {
+        base_name: base_name.to_string(),
+        tried,
+    })
+}
+
+fn start_backend_process(app: &AppHandle) -> Option<SharedChild> {
+    let backend_exe = match resolve_sidecar(app, BACKEND_NAME) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{e}");
+            return None;
+        }
+    };
 
     println!("Starting backend from: {:?}", backend_exe);
 
-    // Start the backend process
-    match Command::new(&backend_exe)
-        .current_dir(&resource_dir)
-        .spawn()
-    {
+    let mut command = Command::new(&backend_exe);
+    if let Some(parent) = backend_exe.parent() {
+        command.current_dir(parent);
+    }
+
+    // Capture stdout/stderr so the output survives the hidden console in
+    // release builds and can be forwarded to the frontend. We use `os_pipe`
+    // rather than `Stdio::piped()` so the reader ends are owned handles that
+    // outlive the `SharedChild`.
+    let (stdout_reader, stdout_writer) = match os_pipe::pipe() {
+        Ok(pair) => pair,
+        Err(e) => {
+            eprintln!("Failed to create stdout pipe: {}", e);
+            return None;
+        }
+    };
+    let (stderr_reader, stderr_writer) = match os_pipe::pipe() {
+        Ok(pair) => pair,
+        Err(e) => {
+            eprintln!("Failed to create stderr pipe: {}", e);
+            return None;
+        }
+    };
+    command.stdout(stdout_writer);
+    command.stderr(stderr_writer);
+
+    // Keep the writer end of stdin so a graceful shutdown can ask the backend
+    // to exit cleanly.
+    let (stdin_reader, stdin_writer) = match os_pipe::pipe() {
+        Ok(pair) => pair,
+        Err(e) => {
+            eprintln!("Failed to create stdin pipe: {}", e);
+            return None;
+        }
+    };
+    command.stdin(stdin_reader);
+
+    match SharedChild::spawn(&mut command) {
         Ok(child) => {
             println!("Backend started with PID: {}", child.id());
+            // Drop our copies of the writer ends so the readers see EOF once the
+            // child exits and closes its own ends.
+            drop(command);
+            if let Some(state) = app.try_state::<BackendProcess>() {
+                if let Ok(mut guard) = state.stdin.lock() {
+                    *guard = Some(stdin_writer);
+                }
+            }
+            spawn_log_reader(app.clone(), "stdout", stdout_reader);
+            spawn_log_reader(app.clone(), "stderr", stderr_reader);
             Some(child)
         }
         Err(e) => {
@@ -64,3 +599,47 @@ fn start_backend_process(app: &tauri::App) -> Option<Child> {
         }
     }
 }
+
+// Read a captured stream line-by-line, forwarding each line on `backend://log`
+// and recording it in the bounded ring buffer.
+fn spawn_log_reader<R: Read + Send + 'static>(app: AppHandle, stream: &'static str, reader: R) {
+    thread::spawn(move || {
+        let mut buf = BufReader::new(reader);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match buf.read_line(&mut line) {
+                Ok(0) => break, // EOF
+                Ok(_) => {
+                    push_log(&app, stream, line.trim_end_matches(['\r', '\n']).to_string());
+                }
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+// Record a log line in the ring buffer and forward it on `backend://log`.
+fn push_log(app: &AppHandle, stream: &'static str, text: String) {
+    let entry = LogLine {
+        stream,
+        text,
+        ts: now_millis(),
+    };
+    if let Some(state) = app.try_state::<BackendProcess>() {
+        if let Ok(mut logs) = state.logs.lock() {
+            if logs.len() == LOG_RING_CAPACITY {
+                logs.pop_front();
+            }
+            logs.push_back(entry.clone());
+        }
+    }
+    let _ = app.emit("backend://log", entry);
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}